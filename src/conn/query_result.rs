@@ -9,11 +9,24 @@
 pub use mysql_common::proto::{Binary, Text};
 
 use mysql_common::packets::OkPacket;
+use serde::de::DeserializeOwned;
 
-use std::{borrow::Cow, marker::PhantomData, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, marker::PhantomData, sync::Arc};
 
 use crate::{conn::ConnMut, Column, Conn, Error, Result, Row};
 
+mod collect_sets;
+mod deserializer;
+mod materialized;
+mod retry;
+mod statements;
+
+pub use self::collect_sets::{CollectSetsError, FromResultSets};
+pub use self::deserializer::{DeserializeRowError, RowDeserializer};
+pub use self::materialized::MaterializedSet;
+pub use self::retry::RetryPolicy;
+pub use self::statements::split_statements;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Or<A, B> {
     A(A),
@@ -37,11 +50,71 @@ impl Protocol for Binary {
     }
 }
 
+/// Columns of a result set together with a name→index map, computed once when
+/// the set is entered so that `SetColumns::column_index` is O(1) on the hot path.
+#[derive(Debug, PartialEq)]
+struct SetMeta {
+    columns: Arc<[Column]>,
+    index: ColumnIndex,
+}
+
+/// Name→index map over a result set's columns. Preserves "first match wins"
+/// semantics for duplicate column names, matching the old linear scan.
+#[derive(Debug, PartialEq)]
+struct ColumnIndex(HashMap<Box<[u8]>, usize>);
+
+impl ColumnIndex {
+    fn build(columns: &[Column]) -> Self {
+        Self::from_names(columns.iter().map(Column::name_ref))
+    }
+
+    /// Builds the index from raw column names, first match wins. Split out from
+    /// `build` so the dedup logic can be unit-tested without a real `Column`.
+    fn from_names<'a>(names: impl Iterator<Item = &'a [u8]>) -> Self {
+        let mut index = HashMap::new();
+        for (i, name) in names.enumerate() {
+            index.entry(name.to_vec().into_boxed_slice()).or_insert(i);
+        }
+        Self(index)
+    }
+
+    fn get(&self, name: &[u8]) -> Option<usize> {
+        self.0.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod column_index_test {
+    use super::ColumnIndex;
+
+    #[test]
+    fn maps_each_name_to_its_position() {
+        let index = ColumnIndex::from_names([b"id".as_slice(), b"name".as_slice()].into_iter());
+        assert_eq!(index.get(b"id"), Some(0));
+        assert_eq!(index.get(b"name"), Some(1));
+    }
+
+    #[test]
+    fn first_match_wins_on_duplicate_names() {
+        let index = ColumnIndex::from_names(
+            [b"id".as_slice(), b"name".as_slice(), b"id".as_slice()].into_iter(),
+        );
+        assert_eq!(index.get(b"id"), Some(0));
+        assert_eq!(index.get(b"name"), Some(1));
+    }
+
+    #[test]
+    fn unknown_name_yields_none() {
+        let index = ColumnIndex::from_names([b"id".as_slice()].into_iter());
+        assert_eq!(index.get(b"missing"), None);
+    }
+}
+
 /// State of a result set iterator.
 #[derive(Debug)]
 enum SetIteratorState {
     /// Iterator is in a non-empty set.
-    InSet(Arc<[Column]>),
+    InSet(Arc<SetMeta>),
     /// Iterator is in an empty set.
     InEmptySet(OkPacket<'static>),
     /// Iterator is in an errored result set.
@@ -61,9 +134,9 @@ impl SetIteratorState {
         }
     }
 
-    fn columns(&self) -> Option<&Arc<[Column]>> {
-        if let Self::InSet(ref cols) = self {
-            Some(cols)
+    fn meta(&self) -> Option<&SetMeta> {
+        if let Self::InSet(ref meta) = self {
+            Some(meta)
         } else {
             None
         }
@@ -72,7 +145,9 @@ impl SetIteratorState {
 
 impl From<Vec<Column>> for SetIteratorState {
     fn from(columns: Vec<Column>) -> Self {
-        Self::InSet(columns.into())
+        let columns: Arc<[Column]> = columns.into();
+        let index = ColumnIndex::build(&columns);
+        Self::InSet(Arc::new(SetMeta { columns, index }))
     }
 }
 
@@ -226,9 +301,79 @@ impl<'c, 't, 'tc, T: crate::prelude::Protocol> QueryResult<'c, 't, 'tc, T> {
     /// Returns columns of the current result rest.
     pub fn columns(&self) -> SetColumns {
         SetColumns {
-            inner: self.state.columns().map(Into::into),
+            inner: self.state.meta(),
         }
     }
+
+    /// Returns an iterator that deserializes each row of the current result set
+    /// directly into `U` via `serde::Deserialize`, using column names as map keys.
+    ///
+    /// Errors if `U` requires a column that is absent from the result set, e.g.
+    /// because of a typo. Where several columns share a name (e.g. `id` from two
+    /// joined tables), only the first of them is exposed to `U`.
+    pub fn deserialize<'d, U: DeserializeOwned>(
+        &'d mut self,
+    ) -> Deserialized<'c, 't, 'tc, 'd, T, U> {
+        Deserialized {
+            inner: self,
+            output: PhantomData,
+        }
+    }
+
+    /// Drives every result set of this response into a typed tuple of `Vec<T>`,
+    /// one element per result set, e.g. `query_result.collect_sets::<(User, Order)>()`.
+    ///
+    /// Errors if the response doesn't carry exactly as many result sets as `U`
+    /// expects.
+    pub fn collect_sets<U: FromResultSets>(
+        &mut self,
+    ) -> std::result::Result<U::Output, CollectSetsError> {
+        U::from_result_sets(self)
+    }
+
+    /// Eagerly drains every result set of this response into an owned, randomly
+    /// addressable `Vec<MaterializedSet>`.
+    ///
+    /// Useful for test harnesses and for procedures where a later set's metadata
+    /// must be inspected before an earlier set's rows are processed — something
+    /// the forward-only `next_set`/`Iterator` API cannot do.
+    ///
+    /// `affected_rows`/`last_insert_id`/`warnings`/`info` are only populated for
+    /// a set with no rows (`INSERT`/`UPDATE`/`DELETE`, ...): those carry an OK
+    /// packet up front. A set with rows (`SELECT`, ...) has its terminating
+    /// OK/EOF packet consumed internally while advancing to the *next* set, so
+    /// this driver never exposes it here; such a `MaterializedSet` reports
+    /// `0`/`None`/empty for these fields, same as `QueryResult` itself would for
+    /// the current set mid-iteration.
+    pub fn into_sets(mut self) -> Result<Vec<MaterializedSet>> {
+        let mut sets = Vec::new();
+
+        while let Some(set) = self.next_set() {
+            let set = set?;
+
+            let columns: Arc<[Column]> = set.columns().as_ref().to_vec().into();
+            let affected_rows = set.affected_rows();
+            let last_insert_id = set.last_insert_id();
+            let warnings = set.warnings();
+            let info = set.info_ref().to_vec();
+
+            let mut rows = Vec::new();
+            for row in set {
+                rows.push(row?);
+            }
+
+            sets.push(MaterializedSet::new(
+                columns,
+                affected_rows,
+                last_insert_id,
+                warnings,
+                info,
+                rows,
+            ));
+        }
+
+        Ok(sets)
+    }
 }
 
 impl<'c, 't, 'tc, T: crate::prelude::Protocol> Drop for QueryResult<'c, 't, 'tc, T> {
@@ -272,9 +417,9 @@ impl<T: crate::prelude::Protocol> Iterator for QueryResult<'_, '_, '_, T> {
         let state = std::mem::replace(&mut self.state, OnBoundary);
 
         match state {
-            InSet(cols) => match T::next(&mut *self.conn, cols.clone()) {
+            InSet(meta) => match T::next(&mut *self.conn, meta.columns.clone()) {
                 Ok(Some(row)) => {
-                    self.state = InSet(cols.clone());
+                    self.state = InSet(meta.clone());
                     Some(Ok(row))
                 }
                 Ok(None) => {
@@ -309,24 +454,52 @@ impl<T: crate::prelude::Protocol> Drop for ResultSet<'_, '_, '_, '_, T> {
     }
 }
 
+/// Iterator over rows of the current result set, deserialized into `U` via `serde`.
+///
+/// Created by [`QueryResult::deserialize`].
+#[derive(Debug)]
+pub struct Deserialized<'a, 'b, 'c, 'd, T: crate::prelude::Protocol, U> {
+    inner: &'d mut QueryResult<'a, 'b, 'c, T>,
+    output: PhantomData<U>,
+}
+
+impl<T, U> Iterator for Deserialized<'_, '_, '_, '_, T, U>
+where
+    T: crate::prelude::Protocol,
+    U: DeserializeOwned,
+{
+    type Item = std::result::Result<U, DeserializeRowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.inner.next()? {
+            Ok(row) => row,
+            Err(err) => return Some(Err(DeserializeRowError::Row(err))),
+        };
+        // Read columns only after a successful row: `state` stays `InSet` with
+        // the same columns across rows of one set, but advances past them once
+        // the set is exhausted, so reading columns before `next()` would either
+        // borrow-conflict with it or see stale/mismatched columns.
+        let columns = self.inner.columns();
+        Some(U::deserialize(RowDeserializer::new(&row, &columns)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SetColumns<'a> {
-    inner: Option<&'a Arc<[Column]>>,
+    inner: Option<&'a SetMeta>,
 }
 
 impl<'a> SetColumns<'a> {
     /// Returns an index of a column by its name.
+    ///
+    /// Backed by a name→index map computed once per result set, so this is O(1)
+    /// regardless of how many columns the set has.
     pub fn column_index<U: AsRef<str>>(&self, name: U) -> Option<usize> {
         let name = name.as_ref().as_bytes();
-        self.inner
-            .as_ref()
-            .and_then(|cols| cols.iter().position(|col| col.name_ref() == name))
+        self.inner.and_then(|meta| meta.index.get(name))
     }
 
     pub fn as_ref(&self) -> &[Column] {
-        self.inner
-            .as_ref()
-            .map(|cols| &(*cols)[..])
-            .unwrap_or(&[][..])
+        self.inner.map(|meta| &meta.columns[..]).unwrap_or(&[][..])
     }
 }
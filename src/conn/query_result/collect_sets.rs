@@ -0,0 +1,187 @@
+// Copyright (c) 2020 rust-mysql-simple contributors
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fmt;
+
+use mysql_common::row::convert::FromRowError;
+
+use crate::{prelude::FromRow, prelude::Protocol, Error, QueryResult};
+
+/// Error produced while collecting a multi-result-set response via
+/// [`QueryResult::collect_sets`].
+#[derive(Debug)]
+pub enum CollectSetsError {
+    /// The connection returned an error while reading a result set.
+    Row(Error),
+    /// A row in a result set could not be converted via `FromRow`.
+    FromRow(FromRowError),
+    /// The response didn't carry as many result sets as the target tuple expects.
+    SetCountMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for CollectSetsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Row(err) => write!(f, "error reading row: {}", err),
+            Self::FromRow(err) => write!(f, "error converting row: {}", err),
+            Self::SetCountMismatch { expected, actual } => write!(
+                f,
+                "expected {} result set(s), but the response carried {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CollectSetsError {}
+
+/// Types whose elements describe the ordered result sets of a `QueryResult`.
+///
+/// Implemented for tuples of `FromRow` types, e.g. `(User, Order)`, letting a
+/// batched statement or a stored procedure call be consumed in one step via
+/// [`QueryResult::collect_sets`] instead of looping over `next_set`/`set_index`
+/// by hand:
+///
+/// ```ignore
+/// let (users, orders) = conn.query_iter(sql)?.collect_sets::<(User, Order)>()?;
+/// ```
+pub trait FromResultSets: Sized {
+    /// `(Vec<A>, Vec<B>, ...)` for a `(A, B, ...)` self type.
+    type Output;
+
+    /// Number of result sets this type expects.
+    const ARITY: usize;
+
+    fn from_result_sets<T: Protocol>(
+        query_result: &mut QueryResult<'_, '_, '_, T>,
+    ) -> Result<Self::Output, CollectSetsError>;
+}
+
+// `collect_one_set`/`ensure_no_more_sets` are exercised only through
+// `FromResultSets::from_result_sets` because both take `&mut QueryResult`,
+// which wraps a live `ConnMut` this crate's snapshot doesn't expose a way to
+// stub out; unlike `split_statements`/`mysql_value_to_iso8601`/`ColumnIndex`,
+// there's no connection-free path to construct one. `CollectSetsError`'s
+// `Display` impl has no such dependency and is covered below.
+
+/// Reads the next result set into a `Vec<A>`, erroring with a set count mismatch
+/// if the response has no more result sets (`index` is the 0-based position of
+/// this set, reported as the actual count).
+fn collect_one_set<A: FromRow, T: Protocol>(
+    query_result: &mut QueryResult<'_, '_, '_, T>,
+    index: usize,
+    arity: usize,
+) -> Result<Vec<A>, CollectSetsError> {
+    let set = query_result
+        .next_set()
+        .ok_or(CollectSetsError::SetCountMismatch {
+            expected: arity,
+            actual: index,
+        })?
+        .map_err(CollectSetsError::Row)?;
+
+    let mut out = Vec::new();
+    for row in set {
+        let row = row.map_err(CollectSetsError::Row)?;
+        out.push(A::from_row_opt(row).map_err(CollectSetsError::FromRow)?);
+    }
+    Ok(out)
+}
+
+/// Errors if the response carries more result sets than `arity` expects.
+fn ensure_no_more_sets<T: Protocol>(
+    query_result: &mut QueryResult<'_, '_, '_, T>,
+    arity: usize,
+) -> Result<(), CollectSetsError> {
+    let mut actual = arity;
+    while query_result.next_set().is_some() {
+        actual += 1;
+    }
+    if actual != arity {
+        return Err(CollectSetsError::SetCountMismatch {
+            expected: arity,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+impl<A: FromRow> FromResultSets for (A,) {
+    type Output = (Vec<A>,);
+    const ARITY: usize = 1;
+
+    fn from_result_sets<T: Protocol>(
+        query_result: &mut QueryResult<'_, '_, '_, T>,
+    ) -> Result<Self::Output, CollectSetsError> {
+        let a = collect_one_set::<A, T>(query_result, 0, Self::ARITY)?;
+        ensure_no_more_sets(query_result, Self::ARITY)?;
+        Ok((a,))
+    }
+}
+
+impl<A: FromRow, B: FromRow> FromResultSets for (A, B) {
+    type Output = (Vec<A>, Vec<B>);
+    const ARITY: usize = 2;
+
+    fn from_result_sets<T: Protocol>(
+        query_result: &mut QueryResult<'_, '_, '_, T>,
+    ) -> Result<Self::Output, CollectSetsError> {
+        let a = collect_one_set::<A, T>(query_result, 0, Self::ARITY)?;
+        let b = collect_one_set::<B, T>(query_result, 1, Self::ARITY)?;
+        ensure_no_more_sets(query_result, Self::ARITY)?;
+        Ok((a, b))
+    }
+}
+
+impl<A: FromRow, B: FromRow, C: FromRow> FromResultSets for (A, B, C) {
+    type Output = (Vec<A>, Vec<B>, Vec<C>);
+    const ARITY: usize = 3;
+
+    fn from_result_sets<T: Protocol>(
+        query_result: &mut QueryResult<'_, '_, '_, T>,
+    ) -> Result<Self::Output, CollectSetsError> {
+        let a = collect_one_set::<A, T>(query_result, 0, Self::ARITY)?;
+        let b = collect_one_set::<B, T>(query_result, 1, Self::ARITY)?;
+        let c = collect_one_set::<C, T>(query_result, 2, Self::ARITY)?;
+        ensure_no_more_sets(query_result, Self::ARITY)?;
+        Ok((a, b, c))
+    }
+}
+
+impl<A: FromRow, B: FromRow, C: FromRow, D: FromRow> FromResultSets for (A, B, C, D) {
+    type Output = (Vec<A>, Vec<B>, Vec<C>, Vec<D>);
+    const ARITY: usize = 4;
+
+    fn from_result_sets<T: Protocol>(
+        query_result: &mut QueryResult<'_, '_, '_, T>,
+    ) -> Result<Self::Output, CollectSetsError> {
+        let a = collect_one_set::<A, T>(query_result, 0, Self::ARITY)?;
+        let b = collect_one_set::<B, T>(query_result, 1, Self::ARITY)?;
+        let c = collect_one_set::<C, T>(query_result, 2, Self::ARITY)?;
+        let d = collect_one_set::<D, T>(query_result, 3, Self::ARITY)?;
+        ensure_no_more_sets(query_result, Self::ARITY)?;
+        Ok((a, b, c, d))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CollectSetsError;
+
+    #[test]
+    fn displays_set_count_mismatch_with_expected_and_actual() {
+        let err = CollectSetsError::SetCountMismatch {
+            expected: 2,
+            actual: 1,
+        };
+        assert_eq!(
+            err.to_string(),
+            "expected 2 result set(s), but the response carried 1"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2020 rust-mysql-simple contributors
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::Value;
+
+use std::{collections::HashSet, fmt};
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, DeserializeSeed, Deserializer, MapAccess, Visitor,
+};
+
+use crate::{Error, Row};
+
+use super::SetColumns;
+
+/// Error produced while deserializing a [`Row`] into a `serde::Deserialize` type.
+#[derive(Debug)]
+pub enum DeserializeRowError {
+    /// The underlying `QueryResult` returned an error before a row could be read.
+    Row(Error),
+    /// The target type asked for a column that isn't present in the result set.
+    MissingColumn(Box<str>),
+    /// Any other deserialization failure, including ones raised by `serde` itself.
+    Custom(String),
+}
+
+impl fmt::Display for DeserializeRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Row(err) => write!(f, "error reading row: {}", err),
+            Self::MissingColumn(name) => write!(f, "missing column `{}`", name),
+            Self::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeRowError {}
+
+impl de::Error for DeserializeRowError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingColumn(field.into())
+    }
+}
+
+/// A `serde::Deserializer` that reads a [`Row`] as a map keyed by its column names.
+///
+/// Construct one via [`QueryResult::deserialize`][deserialize] to map rows directly
+/// into any `Deserialize` struct without going through `FromRow`.
+///
+/// [deserialize]: crate::QueryResult::deserialize
+pub struct RowDeserializer<'a> {
+    row: &'a Row,
+    columns: &'a SetColumns<'a>,
+}
+
+impl<'a> RowDeserializer<'a> {
+    pub(crate) fn new(row: &'a Row, columns: &'a SetColumns<'a>) -> Self {
+        Self { row, columns }
+    }
+}
+
+impl<'de, 'a: 'de> Deserializer<'de> for RowDeserializer<'a> {
+    type Error = DeserializeRowError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(RowMapAccess {
+            columns: self.columns.as_ref().iter().enumerate(),
+            row: self.row,
+            seen: HashSet::new(),
+            pending_index: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+struct RowMapAccess<'a> {
+    columns: std::iter::Enumerate<std::slice::Iter<'a, crate::Column>>,
+    row: &'a Row,
+    /// Column names already yielded as a key, so a repeated name (e.g. two `id`
+    /// columns from a join) is skipped instead of producing a duplicate map key
+    /// that would trip `serde_derive`'s "duplicate field" check. First match wins.
+    seen: HashSet<&'a [u8]>,
+    /// Row position of the key last handed out by `next_key_seed`, consumed by
+    /// the following `next_value_seed` call.
+    pending_index: Option<usize>,
+}
+
+impl<'de, 'a: 'de> MapAccess<'de> for RowMapAccess<'a> {
+    type Error = DeserializeRowError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        for (index, column) in self.columns.by_ref() {
+            let name = column.name_ref();
+            if !self.seen.insert(name) {
+                continue;
+            }
+
+            let name = std::str::from_utf8(name).map_err(|_| {
+                DeserializeRowError::Custom("column name is not valid UTF-8".into())
+            })?;
+
+            self.pending_index = Some(index);
+            return seed
+                .deserialize(BorrowedStrDeserializer::new(name))
+                .map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let index = self
+            .pending_index
+            .take()
+            .expect("next_value_seed called without a preceding next_key_seed");
+        let value = self.row.as_ref(index).ok_or_else(|| {
+            DeserializeRowError::MissingColumn(format!("column #{}", index).into())
+        })?;
+        seed.deserialize(ValueDeserializer { value })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        let (lower, upper) = self.columns.size_hint();
+        upper.or(Some(lower))
+    }
+}
+
+/// A `serde::Deserializer` over a single `mysql_common::Value`.
+struct ValueDeserializer<'a> {
+    value: &'a Value,
+}
+
+impl<'de, 'a: 'de> Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = DeserializeRowError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::NULL => visitor.visit_unit(),
+            Value::Int(v) => visitor.visit_i64(*v),
+            Value::UInt(v) => visitor.visit_u64(*v),
+            Value::Float(v) => visitor.visit_f32(*v),
+            Value::Double(v) => visitor.visit_f64(*v),
+            Value::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(s) => visitor.visit_str(s),
+                Err(_) => visitor.visit_bytes(bytes),
+            },
+            Value::Date(..) | Value::Time(..) => {
+                visitor.visit_string(mysql_value_to_iso8601(self.value))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::NULL => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple map
+        tuple_struct struct enum identifier ignored_any
+    }
+}
+
+/// Renders a `Value::Date`/`Value::Time` as an ISO-8601 string.
+fn mysql_value_to_iso8601(value: &Value) -> String {
+    match *value {
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            if hour == 0 && minute == 0 && second == 0 && micros == 0 {
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            } else {
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+                    year, month, day, hour, minute, second, micros
+                )
+            }
+        }
+        Value::Time(is_neg, days, hours, minutes, seconds, micros) => {
+            let sign = if is_neg { "-" } else { "" };
+            format!(
+                "{}{:02}:{:02}:{:02}.{:06}",
+                sign,
+                u32::from(days) * 24 + u32::from(hours),
+                minutes,
+                seconds,
+                micros
+            )
+        }
+        _ => unreachable!("only called for Date/Time values"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::mysql_value_to_iso8601;
+    use mysql_common::Value;
+
+    #[test]
+    fn formats_midnight_date_without_time_of_day() {
+        assert_eq!(
+            mysql_value_to_iso8601(&Value::Date(2020, 1, 2, 0, 0, 0, 0)),
+            "2020-01-02"
+        );
+    }
+
+    #[test]
+    fn formats_date_with_time_of_day() {
+        assert_eq!(
+            mysql_value_to_iso8601(&Value::Date(2020, 1, 2, 3, 4, 5, 6)),
+            "2020-01-02T03:04:05.000006"
+        );
+    }
+
+    #[test]
+    fn formats_positive_time() {
+        assert_eq!(
+            mysql_value_to_iso8601(&Value::Time(false, 0, 1, 2, 3, 4)),
+            "01:02:03.000004"
+        );
+    }
+
+    #[test]
+    fn formats_negative_time() {
+        assert_eq!(
+            mysql_value_to_iso8601(&Value::Time(true, 0, 1, 2, 3, 4)),
+            "-01:02:03.000004"
+        );
+    }
+
+    #[test]
+    fn folds_days_into_hours() {
+        assert_eq!(
+            mysql_value_to_iso8601(&Value::Time(false, 2, 1, 0, 0, 0)),
+            "49:00:00.000000"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2020 rust-mysql-simple contributors
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{borrow::Cow, ops::Index, sync::Arc};
+
+use crate::{Column, Row};
+
+/// An owned, fully-drained result set.
+///
+/// Unlike [`ResultSet`][super::ResultSet], which is forward-only and tied to the
+/// connection, a `MaterializedSet` can be inspected and revisited in any order
+/// once [`QueryResult::into_sets`][super::QueryResult::into_sets] has drained the
+/// whole response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterializedSet {
+    columns: Arc<[Column]>,
+    affected_rows: u64,
+    last_insert_id: Option<u64>,
+    warnings: u16,
+    info: Vec<u8>,
+    rows: Vec<Row>,
+}
+
+impl MaterializedSet {
+    /// Returns columns of this result set.
+    pub fn columns(&self) -> &Arc<[Column]> {
+        &self.columns
+    }
+
+    /// Returns the number of affected rows, as reported for this result set.
+    ///
+    /// Always `0` for a set with rows (`SELECT`, ...): only a set with no rows
+    /// (`INSERT`/`UPDATE`/`DELETE`, ...) carries this in its OK packet.
+    pub fn affected_rows(&self) -> u64 {
+        self.affected_rows
+    }
+
+    /// Returns the last insert id, as reported for this result set.
+    ///
+    /// Always `None` for a set with rows (`SELECT`, ...); see [`Self::affected_rows`].
+    pub fn last_insert_id(&self) -> Option<u64> {
+        self.last_insert_id
+    }
+
+    /// Returns the warnings count, as reported for this result set.
+    ///
+    /// Always `0` for a set with rows (`SELECT`, ...); see [`Self::affected_rows`].
+    pub fn warnings(&self) -> u16 {
+        self.warnings
+    }
+
+    /// [Info] for this result set.
+    ///
+    /// Always empty for a set with rows (`SELECT`, ...); see [`Self::affected_rows`].
+    ///
+    /// [Info]: http://dev.mysql.com/doc/internals/en/packet-OK_Packet.html
+    pub fn info_ref(&self) -> &[u8] {
+        &self.info
+    }
+
+    /// [Info] for this result set.
+    ///
+    /// Always empty for a set with rows (`SELECT`, ...); see [`Self::affected_rows`].
+    ///
+    /// [Info]: http://dev.mysql.com/doc/internals/en/packet-OK_Packet.html
+    pub fn info_str(&self) -> Cow<str> {
+        String::from_utf8_lossy(&self.info)
+    }
+
+    /// Returns the rows of this result set.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Returns the number of rows in this result set.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns `true` if this result set has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+impl Index<usize> for MaterializedSet {
+    type Output = Row;
+
+    fn index(&self, index: usize) -> &Row {
+        &self.rows[index]
+    }
+}
+
+impl MaterializedSet {
+    pub(crate) fn new(
+        columns: Arc<[Column]>,
+        affected_rows: u64,
+        last_insert_id: Option<u64>,
+        warnings: u16,
+        info: Vec<u8>,
+        rows: Vec<Row>,
+    ) -> Self {
+        Self {
+            columns,
+            affected_rows,
+            last_insert_id,
+            warnings,
+            info,
+            rows,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2020 rust-mysql-simple contributors
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{
+    io::ErrorKind,
+    time::{Duration, Instant},
+};
+
+use crate::{prelude::Protocol, Error, QueryResult, Result};
+
+/// Exponential backoff schedule for [`RetryPolicy::with_retry`].
+///
+/// The default schedule caps the first delay at 50ms and doubles the cap on
+/// every attempt, up to a 30 second overall budget; each actual delay is
+/// uniformly random between zero and its cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    initial_interval: Duration,
+    multiplier: f64,
+    max_elapsed_time: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_millis(50),
+            multiplier: 2.0,
+            max_elapsed_time: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Upper bound of the (jittered) delay before the first retry attempt.
+    /// Defaults to 50ms.
+    pub fn initial_interval(mut self, interval: Duration) -> Self {
+        self.initial_interval = interval;
+        self
+    }
+
+    /// Factor the delay is multiplied by after each attempt. Defaults to `2.0`.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Total time budget across all attempts before giving up and returning the
+    /// last error. Defaults to 30 seconds.
+    pub fn max_elapsed_time(mut self, max_elapsed_time: Duration) -> Self {
+        self.max_elapsed_time = max_elapsed_time;
+        self
+    }
+
+    fn next_delay(&self, attempt: u32, rng_state: &mut u64) -> Duration {
+        let factor = self.multiplier.powi(attempt as i32);
+        let base = self.initial_interval.mul_f64(factor.max(1.0));
+        // Full jitter: a uniformly random delay between zero and `base`.
+        base.mul_f64(next_jitter(rng_state))
+    }
+
+    /// Runs `run` to obtain a `QueryResult`, retrying with exponential backoff
+    /// if it fails with an [`is_transient`] error, until `self.max_elapsed_time`
+    /// is spent, e.g. `RetryPolicy::default().with_retry(|| conn.query_iter(stmt))`.
+    ///
+    /// `run` is responsible for re-establishing the connection before
+    /// re-running the statement: this module only sees `run`'s `QueryResult`,
+    /// never the `Conn`/connection options behind it, so it has no handle to
+    /// reconnect with. A `Conn` opted into automatic reconnection (or a `run`
+    /// that reconnects explicitly before re-querying) will make each retry
+    /// attempt land on a live connection.
+    ///
+    /// Retries only cover obtaining the result set, never reading rows out of
+    /// it, so a partially-read statement is never silently re-executed (which
+    /// would duplicate side effects of non-idempotent statements).
+    pub fn with_retry<'c, 't, 'tc, T, F>(&self, mut run: F) -> Result<QueryResult<'c, 't, 'tc, T>>
+    where
+        T: Protocol,
+        F: FnMut() -> Result<QueryResult<'c, 't, 'tc, T>>,
+    {
+        let start = Instant::now();
+        let mut rng_state = seed_rng();
+        let mut attempt = 0;
+
+        loop {
+            match run() {
+                Ok(query_result) => return Ok(query_result),
+                Err(err) if is_transient(&err) && start.elapsed() < self.max_elapsed_time => {
+                    std::thread::sleep(self.next_delay(attempt, &mut rng_state));
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// A tiny xorshift PRNG, good enough to spread out retries without pulling in a
+/// dependency just for jitter.
+fn next_jitter(state: &mut u64) -> f64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// A seed with real entropy, via the random keys `RandomState` draws from the
+/// OS on construction.
+///
+/// `Instant::elapsed()` called a line after `Instant::now()` is a handful of
+/// nanoseconds — a near-constant seed that would make the jitter sequence
+/// effectively deterministic and correlated across concurrent clients,
+/// defeating its anti-thundering-herd purpose.
+fn seed_rng() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    (RandomState::new().build_hasher().finish()) | 1
+}
+
+/// Returns `true` if `err` is likely transient (a dropped connection) rather
+/// than permanent (a SQL error, auth failure, constraint violation, ...).
+///
+/// A server "gone away"/"lost connection" condition surfaces here too, as a
+/// broken socket rather than a server ERR-packet — the `CR_SERVER_GONE_ERROR`/
+/// `CR_SERVER_LOST` codes are libmysqlclient-side codes that never appear in
+/// `Error::MySqlError`. A graceful shutdown/restart is most commonly seen as
+/// `UnexpectedEof` (read returns 0 after the peer's FIN) or `BrokenPipe`/
+/// `WriteZero` (write after the peer has closed), alongside the already-covered
+/// `ConnectionRefused`/`ConnectionReset`/`ConnectionAborted`.
+///
+/// This snapshot doesn't carry `crate::error`, so whether a malformed/
+/// unexpected-EOF packet is instead surfaced as a `DriverError` variant here
+/// couldn't be verified; only the `std::io::Error` classification below is.
+pub fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::IoError(io_err) => matches!(
+            io_err.kind(),
+            ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::UnexpectedEof
+                | ErrorKind::BrokenPipe
+                | ErrorKind::WriteZero
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_transient, next_jitter};
+    use crate::Error;
+    use std::io::ErrorKind;
+
+    fn io_err(kind: ErrorKind) -> Error {
+        Error::IoError(std::io::Error::from(kind))
+    }
+
+    #[test]
+    fn classifies_each_known_transient_io_kind() {
+        for kind in [
+            ErrorKind::ConnectionRefused,
+            ErrorKind::ConnectionReset,
+            ErrorKind::ConnectionAborted,
+            ErrorKind::UnexpectedEof,
+            ErrorKind::BrokenPipe,
+            ErrorKind::WriteZero,
+        ] {
+            assert!(
+                is_transient(&io_err(kind)),
+                "{:?} should be transient",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn does_not_classify_other_io_kinds_as_transient() {
+        for kind in [
+            ErrorKind::NotFound,
+            ErrorKind::PermissionDenied,
+            ErrorKind::InvalidInput,
+        ] {
+            assert!(
+                !is_transient(&io_err(kind)),
+                "{:?} should not be transient",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn next_jitter_stays_within_unit_interval() {
+        let mut state = 0x1234_5678_9abc_def1_u64;
+        for _ in 0..100 {
+            let jitter = next_jitter(&mut state);
+            assert!((0.0..1.0).contains(&jitter), "{} out of range", jitter);
+        }
+    }
+
+    #[test]
+    fn next_jitter_advances_the_rng_state() {
+        let mut state = 0x1234_5678_9abc_def1_u64;
+        let first = next_jitter(&mut state);
+        let second = next_jitter(&mut state);
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2020 rust-mysql-simple contributors
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Normal,
+    SingleQuoted,
+    DoubleQuoted,
+    Backtick,
+    LineComment,
+    BlockComment,
+}
+
+/// Splits a SQL script into its individual statements at top-level `;` boundaries,
+/// for use with [`QueryResult`][crate::QueryResult] when `CLIENT_MULTI_STATEMENTS`
+/// isn't set and each statement must be submitted on its own, e.g. a `.sql`
+/// migration file.
+///
+/// Unlike a naive `sql.split(';')`, this never splits inside a single-quoted
+/// string, a double-quoted or backtick-quoted identifier (honoring doubled-quote
+/// escapes in all three, and `\`-escapes in single- and double-quoted ones), a
+/// `#` line comment, a `--` line comment (`--` followed by whitespace or the end
+/// of input), or a `/* */` block comment. Trailing empty statements, such as the one after a final `;`, are
+/// dropped.
+pub fn split_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut state = State::Normal;
+    let mut start = 0;
+    let mut statements = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes[i];
+
+        match state {
+            State::Normal => match byte {
+                b'\'' => state = State::SingleQuoted,
+                b'"' => state = State::DoubleQuoted,
+                b'`' => state = State::Backtick,
+                b'#' => state = State::LineComment,
+                b'-' if bytes.get(i + 1) == Some(&b'-')
+                    && bytes.get(i + 2).map_or(true, u8::is_ascii_whitespace) =>
+                {
+                    state = State::LineComment;
+                    i += 1;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    state = State::BlockComment;
+                    i += 1;
+                }
+                b';' => {
+                    push_statement(&mut statements, &sql[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            },
+            State::SingleQuoted => match byte {
+                b'\\' => i += 1,
+                b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 1,
+                b'\'' => state = State::Normal,
+                _ => {}
+            },
+            State::DoubleQuoted => match byte {
+                b'\\' => i += 1,
+                b'"' if bytes.get(i + 1) == Some(&b'"') => i += 1,
+                b'"' => state = State::Normal,
+                _ => {}
+            },
+            State::Backtick => match byte {
+                b'`' if bytes.get(i + 1) == Some(&b'`') => i += 1,
+                b'`' => state = State::Normal,
+                _ => {}
+            },
+            State::LineComment => {
+                if byte == b'\n' {
+                    state = State::Normal;
+                }
+            }
+            State::BlockComment => {
+                if byte == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                    state = State::Normal;
+                    i += 1;
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    push_statement(&mut statements, &sql[start..]);
+    statements
+}
+
+fn push_statement<'a>(statements: &mut Vec<&'a str>, statement: &'a str) {
+    let trimmed = statement.trim();
+    if !trimmed.is_empty() {
+        statements.push(trimmed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_statements;
+
+    #[test]
+    fn splits_on_top_level_semicolons() {
+        assert_eq!(
+            split_statements("SELECT 1; SELECT 2"),
+            vec!["SELECT 1", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn drops_trailing_empty_statement() {
+        assert_eq!(split_statements("SELECT 1;"), vec!["SELECT 1"]);
+        assert_eq!(split_statements("SELECT 1;  ;  "), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn ignores_semicolon_in_single_quoted_string() {
+        assert_eq!(
+            split_statements("SELECT ';'; SELECT 2"),
+            vec!["SELECT ';'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn honors_doubled_quote_escape_in_single_quoted_string() {
+        assert_eq!(
+            split_statements("SELECT 'it''s; me'; SELECT 2"),
+            vec!["SELECT 'it''s; me'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn honors_backslash_escape_in_single_quoted_string() {
+        assert_eq!(
+            split_statements(r"SELECT 'a\'; b'; SELECT 2"),
+            vec![r"SELECT 'a\'; b'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolon_in_double_quoted_identifier() {
+        assert_eq!(
+            split_statements(r#"SELECT "a;b"; SELECT 2"#),
+            vec![r#"SELECT "a;b""#, "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolon_in_backtick_identifier_with_doubled_backtick() {
+        assert_eq!(
+            split_statements("SELECT `a``;b`; SELECT 2"),
+            vec!["SELECT `a``;b`", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolon_in_line_comment() {
+        assert_eq!(
+            split_statements("SELECT 1; -- comment; with semicolons\nSELECT 2"),
+            vec!["SELECT 1", "-- comment; with semicolons\nSELECT 2"]
+        );
+        assert_eq!(
+            split_statements("SELECT 1; # comment; with semicolons\nSELECT 2"),
+            vec!["SELECT 1", "# comment; with semicolons\nSELECT 2"]
+        );
+    }
+
+    #[test]
+    fn requires_whitespace_after_double_dash_to_start_a_comment() {
+        // No whitespace after `--`: not a comment, so the `;` still splits.
+        assert_eq!(
+            split_statements("SELECT 1--2; SELECT 3"),
+            vec!["SELECT 1--2", "SELECT 3"]
+        );
+        // Whitespace after `--`: a real comment, so its `;` is swallowed.
+        assert_eq!(
+            split_statements("SELECT 1 -- 2; SELECT 3"),
+            vec!["SELECT 1 -- 2; SELECT 3"]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolon_in_block_comment() {
+        assert_eq!(
+            split_statements("SELECT 1; /* comment; with semicolons */ SELECT 2"),
+            vec!["SELECT 1", "/* comment; with semicolons */ SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn empty_input_yields_no_statements() {
+        assert!(split_statements("").is_empty());
+        assert!(split_statements("   ").is_empty());
+    }
+}